@@ -1,36 +1,43 @@
 extern crate unarr_sys;
+extern crate filetime;
 
 #[cfg(not(feature = "no_guess"))]
 extern crate chardet;
 #[cfg(not(feature = "no_guess"))]
-extern crate encoding;
+extern crate encoding_rs;
 #[cfg(not(feature = "no_guess"))]
 extern crate codepage_437;
 
 use unarr_sys::ffi::*;
 
-#[cfg(not(feature = "no_guess"))]
-use encoding::label::encoding_from_whatwg_label;
-#[cfg(not(feature = "no_guess"))]
-use encoding::DecoderTrap;
-
-
-
 #[cfg(not(feature = "no_guess"))]
 use codepage_437::{ToCp437, CP437_WINGDINGS};
 
 use std::{
     ffi::{CStr, CString},
+    io::{Read, Seek, SeekFrom},
+    os::raw::c_int,
     path::Path,
 };
 
-const SKIP_BUF_SIZE: usize = 1024 * 1024 * 1024;
+// unarr's custom-stream whence codes follow the usual C fseek convention
+const AR_SEEK_SET: c_int = 0;
+const AR_SEEK_CUR: c_int = 1;
+const AR_SEEK_END: c_int = 2;
+
+//seeking forward (including replaying up to `readed` on resume()) discards
+//bytes through this buffer; keep it small since a single Seek::seek of a
+//handful of bytes shouldn't pay for a gigabyte-sized allocation
+const SKIP_BUF_SIZE: usize = 64 * 1024;
 type Cookie = u64;
 const INVALID_READER_COOKIE: Cookie = 0;
 
 pub struct ArStream {
     ptr: p_ar_stream,
     mem: Option<Vec<u8>>,
+    // keeps a custom Read+Seek backend alive for the archive's lifetime;
+    // the trampolines below only borrow it through the raw userdata pointer
+    reader: Option<Box<dyn std::any::Any>>,
 }
 
 pub struct EntryReader<'a> {
@@ -57,6 +64,29 @@ impl<'a> Drop for EntryReader<'a> {
 }
 
 impl<'a> EntryReader<'a> {
+    //uncompress and discard `skip` bytes from the decoder's current position
+    unsafe fn skip_forward(&mut self, mut skip: size_t) -> std::io::Result<()> {
+        if self.skip_buf.is_null() && skip > 0 {
+            //lazy create a small buffer to skip bytes
+            //maybe we can use stack buf to avoid this, but stack
+            //maybe too small for quickly unpack enough bytes
+            self.skip_buf = std::alloc::alloc(skip_buf_layout());
+        }
+
+        while skip > 0 {
+            let to_read = skip.min(SKIP_BUF_SIZE);
+            if !ar_entry_uncompress(self.archive.ptr, self.skip_buf as *mut c_void, to_read) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "skip buffer failed",
+                ));
+            }
+            skip -= to_read;
+        }
+
+        Ok(())
+    }
+
     unsafe fn resume(&mut self) -> std::io::Result<()> {
         let need_reset_pos = (ar_entry_get_offset(self.archive.ptr) != self.entry_offset)
             || (self.archive.last_reader_cookie.get() != self.cookie);
@@ -72,27 +102,52 @@ impl<'a> EntryReader<'a> {
             ));
         }
         //must resume last read pos. read up to readed bytes
-        //allocate temp memory to write unused bytes
-        if self.skip_buf.is_null() && self.readed > 0 {
-            //lazy create a 1MB buffer to skip bytes
-            //maybe we can use stack buf to avoid this, but stack
-            //maybe too small for quickly unpack enough bytes
-            self.skip_buf = std::alloc::alloc(skip_buf_layout());
+        self.skip_forward(self.readed)
+    }
+}
+
+//computes the clamped, absolute target position for a seek, independent of
+//any archive/FFI state so the arithmetic can be unit tested on its own
+fn seek_target(size: size_t, readed: size_t, pos: std::io::SeekFrom) -> std::io::Result<size_t> {
+    let target = match pos {
+        //clamp before the i64 cast: n may exceed i64::MAX and would
+        //otherwise wrap negative and be rejected as InvalidInput
+        std::io::SeekFrom::Start(n) => n.min(size as u64) as i64,
+        std::io::SeekFrom::End(e) => size as i64 + e,
+        std::io::SeekFrom::Current(c) => readed as i64 + c,
+    };
+
+    if target < 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "invalid seek to a negative position",
+        ));
+    }
+
+    Ok((target as size_t).min(size))
+}
+
+impl<'a> std::io::Seek for EntryReader<'a> {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        let target = seek_target(self.size, self.readed, pos)?;
+
+        if target < self.readed {
+            //decoder already consumed past target, rewind to the entry's
+            //start; invalidating the cookie forces resume() to reparse
+            //and replay from offset 0
+            self.archive.last_reader_cookie.set(INVALID_READER_COOKIE);
+            self.readed = 0;
         }
 
-        let mut skip = self.readed;
-        while skip > 0 {
-            let to_read = skip.min(SKIP_BUF_SIZE);
-            if !ar_entry_uncompress(self.archive.ptr, self.skip_buf as *mut c_void, to_read) {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    "skip buffer failed",
-                ));
-            }
-            skip -= to_read;
+        unsafe {
+            self.resume()?;
+            self.skip_forward(target - self.readed)?;
         }
 
-        Ok(())
+        self.readed = target;
+        assert!(self.readed <= self.size);
+
+        Ok(self.readed as u64)
     }
 }
 
@@ -140,8 +195,10 @@ pub struct ArArchive {
     stream: std::mem::ManuallyDrop<ArStream>,
     cookie_counter: std::cell::Cell<Cookie>,
     last_reader_cookie: std::cell::Cell<Cookie>,
-    #[cfg(feature = "chardet")]
+    #[cfg(not(feature = "no_guess"))]
     format: ArchiveFormat,
+    #[cfg(not(feature = "no_guess"))]
+    encoding_mode: EncodingMode,
 }
 
 unsafe impl Send for ArArchive {}
@@ -182,13 +239,18 @@ impl ArStream {
             ));
         }
 
-        Ok(ArStream { ptr, mem: None })
+        Ok(ArStream {
+            ptr,
+            mem: None,
+            reader: None,
+        })
     }
 
     pub fn from_memory(memory: Vec<u8>) -> ArStream {
         let mut ret = ArStream {
             ptr: std::ptr::null(),
             mem: Some(memory),
+            reader: None,
         };
 
         let p: p_ar_stream;
@@ -203,6 +265,86 @@ impl ArStream {
 
         ret
     }
+
+    /// Bridges an arbitrary `Read + Seek` into unarr's custom-stream callback
+    /// interface, so an archive can be driven from a socket, a wrapped
+    /// decompressor, a mmap, or anything else that isn't a plain file or an
+    /// in-memory buffer.
+    pub fn from_reader<R: Read + Seek + 'static>(reader: R) -> ArStream {
+        let mut boxed: Box<R> = Box::new(reader);
+        let data = boxed.as_mut() as *mut R as *mut c_void;
+
+        let ptr: p_ar_stream;
+        unsafe {
+            //unarr's custom-stream constructor is ar_open_stream, taking the
+            //userdata pointer and the callbacks in (close, read, seek, tell)
+            //order
+            ptr = ar_open_stream(
+                data,
+                custom_close::<R>,
+                custom_read::<R>,
+                custom_seek::<R>,
+                custom_tell::<R>,
+            );
+        }
+
+        ArStream {
+            ptr,
+            mem: None,
+            reader: Some(boxed),
+        }
+    }
+}
+
+//like POSIX read(): 0 means clean EOF, (size_t)-1 signals a hard error so
+//unarr doesn't mistake a transient I/O failure for a short/empty archive
+unsafe extern "C" fn custom_read<R: Read>(
+    data: *mut c_void,
+    buffer: *mut c_void,
+    count: size_t,
+) -> size_t {
+    let reader = &mut *(data as *mut R);
+    let buf = std::slice::from_raw_parts_mut(buffer as *mut u8, count);
+
+    //like fread, keep reading until the buffer is full or we hit EOF/an error
+    let mut total = 0;
+    while total < buf.len() {
+        match reader.read(&mut buf[total..]) {
+            Ok(0) => break,
+            Ok(n) => total += n,
+            Err(_) => return size_t::MAX,
+        }
+    }
+
+    total
+}
+
+unsafe extern "C" fn custom_seek<R: Seek>(data: *mut c_void, offset: off64_t, whence: c_int) -> bool {
+    let reader = &mut *(data as *mut R);
+
+    let pos = match whence {
+        AR_SEEK_SET => SeekFrom::Start(offset as u64),
+        AR_SEEK_CUR => SeekFrom::Current(offset),
+        AR_SEEK_END => SeekFrom::End(offset),
+        _ => return false,
+    };
+
+    reader.seek(pos).is_ok()
+}
+
+//returns -1 on failure rather than silently reporting position 0, which
+//would look like a valid (if wrong) offset and could desync unarr's parser
+unsafe extern "C" fn custom_tell<R: Seek>(data: *mut c_void) -> off64_t {
+    let reader = &mut *(data as *mut R);
+    match reader.seek(SeekFrom::Current(0)) {
+        Ok(pos) => pos as off64_t,
+        Err(_) => -1,
+    }
+}
+
+unsafe extern "C" fn custom_close<R>(_data: *mut c_void) {
+    //no-op: the boxed reader is owned by ArStream and is dropped by Rust
+    //once ArStream itself drops, right after ar_close() runs
 }
 
 #[derive(Copy, Clone)]
@@ -213,6 +355,18 @@ pub enum ArchiveFormat {
     Tar,
 }
 
+/// Controls how raw (possibly non-UTF8) entry names are decoded.
+#[cfg(not(feature = "no_guess"))]
+#[derive(Copy, Clone)]
+pub enum EncodingMode {
+    /// Guess the charset with `chardet`, same as before this enum existed.
+    Auto,
+    /// Always decode with the given charset, skipping the guess entirely.
+    Force(&'static encoding_rs::Encoding),
+    /// Don't re-decode at all; return the bytes unarr gave us as-is.
+    Raw,
+}
+
 impl ArArchive {
     pub fn iter(&self) -> ArArchiveIterator {
         ArArchiveIterator {
@@ -253,7 +407,30 @@ impl ArArchive {
         Ok(ret)
     }
 
+    #[cfg(not(feature = "no_guess"))]
     pub fn new(stream: ArStream, try_format: Option<ArchiveFormat>) -> std::io::Result<ArArchive> {
+        Self::with_encoding(stream, try_format, EncodingMode::Auto)
+    }
+
+    #[cfg(feature = "no_guess")]
+    pub fn new(stream: ArStream, try_format: Option<ArchiveFormat>) -> std::io::Result<ArArchive> {
+        Self::open(stream, try_format)
+    }
+
+    /// Like [`ArArchive::new`], but lets the caller pick the filename-decoding
+    /// policy instead of always falling back to `chardet`'s statistical guess.
+    #[cfg(not(feature = "no_guess"))]
+    pub fn with_encoding(
+        stream: ArStream,
+        try_format: Option<ArchiveFormat>,
+        mode: EncodingMode,
+    ) -> std::io::Result<ArArchive> {
+        let mut ret = Self::open(stream, try_format)?;
+        ret.encoding_mode = mode;
+        Ok(ret)
+    }
+
+    fn open(stream: ArStream, try_format: Option<ArchiveFormat>) -> std::io::Result<ArArchive> {
         let mut ptr: p_ar_archive;
 
         let mut tries = vec![];
@@ -290,8 +467,10 @@ impl ArArchive {
                     stream: std::mem::ManuallyDrop::new(stream),
                     cookie_counter: std::cell::Cell::new(INVALID_READER_COOKIE),
                     last_reader_cookie: std::cell::Cell::new(INVALID_READER_COOKIE),
-                    #[cfg(feature = "chardet")]
+                    #[cfg(not(feature = "no_guess"))]
                     format: *try_format,
+                    #[cfg(not(feature = "no_guess"))]
+                    encoding_mode: EncodingMode::Auto,
                 });
             }
         }
@@ -301,6 +480,21 @@ impl ArArchive {
             "create archive failed",
         ))
     }
+
+    /// Extracts every entry into `dst`, creating it and any intermediate
+    /// directories as needed. Modeled on `tar::Archive::unpack`. Returns the
+    /// number of entries written.
+    pub fn unpack<P: AsRef<Path>>(&self, dst: P) -> std::io::Result<usize> {
+        let dst = dst.as_ref();
+        let mut count = 0;
+
+        for entry in self.iter() {
+            entry.unpack_in(self, dst)?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
 }
 
 pub struct ArArchiveIterator<'a> {
@@ -333,12 +527,95 @@ impl ArEntry {
     pub fn time(&self) -> time64_t {
         self.time
     }
+
+    /// Extracts this entry into `dst`, rooted at `dst` itself. Modeled on
+    /// `tar::Entry::unpack_in`: the stored name is resolved relative to
+    /// `dst`, and any entry that would escape it (absolute path, `..`
+    /// component, or a symlinked parent that leads outside) is rejected.
+    pub fn unpack_in<P: AsRef<Path>>(&self, archive: &ArArchive, dst: P) -> std::io::Result<()> {
+        let target = sanitized_dest(dst.as_ref(), self.name())?;
+
+        //zip/tar represent directories as a zero-size entry whose stored
+        //name ends with a path separator; just create the directory,
+        //there's no content or mtime to restore
+        if is_dir_entry_name(self.name()) {
+            std::fs::create_dir_all(&target)?;
+            return Ok(());
+        }
+
+        let mut reader = archive.reader_for(self)?;
+        let mut file = std::fs::File::create(&target)?;
+        std::io::copy(&mut reader, &mut file)?;
+        drop(file);
+
+        filetime::set_file_mtime(&target, filetime::FileTime::from_unix_time(self.time, 0))?;
+
+        Ok(())
+    }
+}
+
+fn is_dir_entry_name(name: &str) -> bool {
+    name.ends_with('/') || name.ends_with('\\')
+}
+
+// rejects absolute paths and `..` components, then re-checks the resolved
+// parent directory against the canonical destination so a symlink planted
+// inside `dst` can't be used to escape it either (zip-slip protection)
+fn sanitized_dest(dst: &Path, name: &str) -> std::io::Result<std::path::PathBuf> {
+    let rel = Path::new(name);
+
+    let escapes_err = || {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("entry name escapes destination: {}", name),
+        )
+    };
+
+    //an entry must name something under dst, not dst itself: reject
+    //absolute paths, any `..` component, and names like "." that carry no
+    //real (Normal) path segment at all
+    let mut has_normal_component = false;
+    for component in rel.components() {
+        match component {
+            std::path::Component::Normal(_) => has_normal_component = true,
+            std::path::Component::CurDir => {}
+            _ => return Err(escapes_err()),
+        }
+    }
+    if !has_normal_component {
+        return Err(escapes_err());
+    }
+
+    let target = dst.join(rel);
+
+    //defense in depth: the checks above should already guarantee a proper
+    //file name, but never panic on attacker-controlled input if they don't
+    let file_name = target.file_name().ok_or_else(escapes_err)?.to_owned();
+    let parent = target.parent().ok_or_else(escapes_err)?;
+
+    std::fs::create_dir_all(&dst)?;
+    std::fs::create_dir_all(parent)?;
+
+    let dst_canon = dst.canonicalize()?;
+    let parent_canon = parent.canonicalize()?;
+
+    if !parent_canon.starts_with(&dst_canon) {
+        return Err(escapes_err());
+    }
+
+    Ok(parent_canon.join(file_name))
 }
 
 #[cfg(not(feature = "no_guess"))]
-fn zip_guess_name(cstr: &CStr) -> Option<String> {
+fn zip_guess_name(cstr: &CStr, mode: &EncodingMode) -> Option<String> {
+
+    if let EncodingMode::Raw = mode {
+        //caller asked for the bytes untouched, skip the cp437 roundtrip
+        //and any guessing entirely
+        return Some(cstr.to_string_lossy().into_owned());
+    }
 
-    //try convert back to raw string. original input can be 
+    //try convert back to raw string. original input can be
     //just UTF8 or incorrected converted to utf8 from cp437
     //
     let try_as_utf8 = cstr.to_str();
@@ -355,32 +632,35 @@ fn zip_guess_name(cstr: &CStr) -> Option<String> {
         // so this utf8 can't be converted from cp437
 
         return Some(try_as_utf8.unwrap().to_owned());
-        
+
     }
 
     //successfully convert back to cp437 , two possible:
     //1. raw string from zip is just ascii, convert to utf8 without change value
     //2. raw string is not cp437, and has other character which is not normal
-    //    file name char. 
-    //no matter which condition, we just guess encoding by chardet
+    //    file name char.
+    //no matter which condition, we decode with whatever encoding the policy gives us
 
     let cp437bin = try_back_437.unwrap();
 
-    //guess encoding
-    let result = chardet::detect(&cp437bin);
-    // result.0 Encode
-    // result.1 Confidence
-    // result.2 Language
-
-    // decode file into utf-8
-    let dec = encoding_from_whatwg_label(chardet::charset2encoding(&result.0))?;
+    let encoding = match mode {
+        EncodingMode::Force(encoding) => *encoding,
+        EncodingMode::Auto => {
+            //guess encoding
+            let result = chardet::detect(&cp437bin);
+            // result.0 Encode
+            // result.1 Confidence
+            // result.2 Language
+            encoding_rs::Encoding::for_label(chardet::charset2encoding(&result.0).as_bytes())?
+        }
+        EncodingMode::Raw => unreachable!("handled above"),
+    };
 
-    let decoded = dec.decode(&cp437bin, DecoderTrap::Ignore);
-    if decoded.is_err() {
-        return None;
-    }
+    // decode file into utf-8; encoding_rs never fails, it substitutes
+    // the replacement character for anything it can't map
+    let (decoded, _, _) = encoding.decode(&cp437bin);
 
-    Some(decoded.unwrap())
+    Some(decoded.into_owned())
 }
 
 impl<'a> Iterator for ArArchiveIterator<'a> {
@@ -417,81 +697,198 @@ impl<'a> Iterator for ArArchiveIterator<'a> {
 
         //now we already parsed a entry
 
-        let name: String;
+        let ret = unsafe { current_entry(self.archive) };
 
-        let offset: off64_t;
-        let size: size_t;
-        let time: time64_t;
-        unsafe {
-            let c_name = ar_entry_get_name(self.archive.ptr);
-            assert!(!c_name.is_null());
+        assert!(ret.offset == 0 || (ret.offset > self.entry_offset));
+        self.entry_offset = ret.offset;
 
-            #[cfg(not(feature = "no_guess"))]
-            {
-                let c_str = CStr::from_ptr(c_name);
-                if let ArchiveFormat::Zip = self.archive.format {
-                    let guessed = zip_guess_name(c_str);
-                    if guessed.is_none() {
-                        //unarr try to decode as CP437 if not a utf8
-                        //encoding so we can assume the string is utf8
-                        //encoded (all value has corresponding utf8 represent)
-                        name = c_str.to_str().unwrap().to_string();
-                    } else {
-                        name = guessed.unwrap();
-                    }
-                } else {
-                    name = c_str.to_str().unwrap().to_string();
-                }
-            }
+        Some(ret)
+    }
+}
 
-            #[cfg(feature = "no_guess")]
-            {
-                name = CStr::from_ptr(c_name).to_string_lossy();
+//reads the name of whatever entry unarr currently has parsed
+unsafe fn current_entry_name(archive: &ArArchive) -> String {
+    let c_name = ar_entry_get_name(archive.ptr);
+    assert!(!c_name.is_null());
+
+    let name: String;
+
+    #[cfg(not(feature = "no_guess"))]
+    {
+        let c_str = CStr::from_ptr(c_name);
+        if let ArchiveFormat::Zip = archive.format {
+            let guessed = zip_guess_name(c_str, &archive.encoding_mode);
+            if guessed.is_none() {
+                //unarr try to decode as CP437 if not a utf8
+                //encoding so we can assume the string is utf8
+                //encoded (all value has corresponding utf8 represent)
+                name = c_str.to_str().unwrap().to_string();
+            } else {
+                name = guessed.unwrap();
             }
+        } else {
+            name = c_str.to_str().unwrap().to_string();
+        }
+    }
+
+    #[cfg(feature = "no_guess")]
+    {
+        name = CStr::from_ptr(c_name).to_string_lossy();
+    }
+
+    name
+}
 
-            offset = ar_entry_get_offset(self.archive.ptr);
-            size = ar_entry_get_size(self.archive.ptr);
-            time = ar_entry_get_filetime(self.archive.ptr);
+//builds an ArEntry from whatever entry unarr currently has parsed
+unsafe fn current_entry(archive: &ArArchive) -> ArEntry {
+    let name = current_entry_name(archive);
+
+    ArEntry {
+        name,
+        offset: ar_entry_get_offset(archive.ptr),
+        size: ar_entry_get_size(archive.ptr),
+        time: ar_entry_get_filetime(archive.ptr),
+        #[cfg(debug_assertions)]
+        ptr: archive.ptr,
+    }
+}
+
+/// A forward-only walk over every entry of a solid archive, fully
+/// uncompressing each one exactly once. `reader_for`/`EntryReader::resume`
+/// replay the whole decompressed prefix on every rewind, so random access
+/// into a solid 7z/RAR degrades to O(n^2) over a full extraction; this type
+/// instead parses entries strictly in offset order and never rewinds,
+/// giving linear-time extraction at the cost of losing random access.
+pub struct SequentialExtractor {
+    archive: ArArchive,
+    started: bool,
+}
+
+impl ArArchive {
+    /// Consumes the archive for a forward-only, linear-time walk. See
+    /// [`SequentialExtractor`].
+    pub fn sequential(self) -> SequentialExtractor {
+        SequentialExtractor {
+            archive: self,
+            started: false,
         }
+    }
+}
 
-        let ret = ArEntry {
-            name,
-            offset,
-            size,
-            time,
-            #[cfg(debug_assertions)]
-            ptr: self.archive.ptr,
+impl SequentialExtractor {
+    /// Parses the next entry and fully uncompresses it into `buf` (cleared
+    /// and resized as needed, so callers can reuse the same buffer across
+    /// calls). Returns `Ok(None)` once the archive is exhausted. Entries
+    /// must be consumed strictly in order; this is not a general-purpose
+    /// replacement for `ArArchive::reader_for`.
+    pub fn advance(&mut self, buf: &mut Vec<u8>) -> std::io::Result<Option<ArEntry>> {
+        let parse_ok = unsafe {
+            if !self.started {
+                self.started = true;
+                ar_parse_entry_at(self.archive.ptr, 0)
+            } else {
+                ar_parse_entry(self.archive.ptr)
+            }
         };
 
-        assert!(ret.offset == 0 || (ret.offset > self.entry_offset));
-        self.entry_offset = ret.offset;
+        if !parse_ok {
+            return Ok(None);
+        }
 
-        Some(ret)
+        let entry = unsafe { current_entry(&self.archive) };
+
+        buf.clear();
+        buf.resize(entry.size, 0);
+
+        unsafe {
+            if entry.size > 0
+                && !ar_entry_uncompress(self.archive.ptr, buf.as_mut_ptr() as *mut c_void, entry.size)
+            {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    "failed to uncompress entry",
+                ));
+            }
+        }
+
+        Ok(Some(entry))
     }
 }
 
 #[cfg(test)]
 extern crate rand;
 
+#[cfg(test)]
 mod tests {
 
     use super::*;
-    use std::io::Read;
+    use std::io::{Read, Seek, SeekFrom};
+
+    #[test]
+    fn seek_target_clamps_start_to_size() {
+        assert_eq!(
+            seek_target(10, 3, std::io::SeekFrom::Start(100)).unwrap(),
+            10
+        );
+    }
+
+    #[test]
+    fn seek_target_clamps_huge_start_without_wrapping_negative() {
+        // a naive `n as i64` cast on n > i64::MAX wraps negative and gets
+        // rejected as InvalidInput; clamping to size first must avoid that
+        assert_eq!(
+            seek_target(10, 0, std::io::SeekFrom::Start(u64::MAX)).unwrap(),
+            10
+        );
+    }
+
+    #[test]
+    fn seek_target_end_and_current() {
+        assert_eq!(seek_target(10, 4, std::io::SeekFrom::End(-2)).unwrap(), 8);
+        assert_eq!(
+            seek_target(10, 4, std::io::SeekFrom::Current(3)).unwrap(),
+            7
+        );
+    }
+
+    #[test]
+    fn seek_target_rejects_negative_result() {
+        assert!(seek_target(10, 4, std::io::SeekFrom::Current(-5)).is_err());
+    }
 
     #[test]
     fn test_encoding() {
-        let ar = ArArchive::new(
-            ArStream::from_file("/home/comicfans/Downloads/中债国债到期收益率.zip")
-                .unwrap(),
-            None,
-        )
-        .unwrap();
+        let mut d = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("tests/cjk_names.zip");
+
+        let ar = ArArchive::new(ArStream::from_file(d).unwrap(), None).unwrap();
 
         for ent in ar.iter() {
             println!("{}", ent.name());
         }
     }
 
+    #[cfg(not(feature = "no_guess"))]
+    #[test]
+    fn zip_guess_name_raw_mode_returns_untouched_bytes() {
+        let c = CString::new("plain.txt").unwrap();
+        assert_eq!(zip_guess_name(&c, &EncodingMode::Raw).unwrap(), "plain.txt");
+    }
+
+    #[cfg(not(feature = "no_guess"))]
+    #[test]
+    fn zip_guess_name_force_mode_decodes_with_given_encoding() {
+        // ASCII-range bytes round-trip identically through cp437 and decode
+        // identically under any Windows code page, so this confirms Force
+        // mode actually uses the supplied encoding instead of falling
+        // through to the chardet guess, without depending on any
+        // codepage_437-specific byte mapping.
+        let c = CString::new("report.txt").unwrap();
+        let decoded =
+            zip_guess_name(&c, &EncodingMode::Force(encoding_rs::WINDOWS_1252)).unwrap();
+        assert_eq!(decoded, "report.txt");
+    }
+
     #[test]
     fn test() {
         let mut d = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -523,4 +920,109 @@ mod tests {
             assert_eq!(outer_buf, inner_vec[i]);
         }
     }
+
+    #[test]
+    fn custom_read_seek_tell_roundtrip_over_a_cursor() {
+        let mut boxed: Box<std::io::Cursor<Vec<u8>>> =
+            Box::new(std::io::Cursor::new(b"hello world".to_vec()));
+        let data = boxed.as_mut() as *mut std::io::Cursor<Vec<u8>> as *mut c_void;
+
+        let mut buf = [0u8; 5];
+        let n = unsafe {
+            custom_read::<std::io::Cursor<Vec<u8>>>(data, buf.as_mut_ptr() as *mut c_void, buf.len())
+        };
+        assert_eq!(n, 5);
+        assert_eq!(&buf, b"hello");
+        assert_eq!(unsafe { custom_tell::<std::io::Cursor<Vec<u8>>>(data) }, 5);
+
+        assert!(unsafe { custom_seek::<std::io::Cursor<Vec<u8>>>(data, 0, AR_SEEK_SET) });
+        assert_eq!(unsafe { custom_tell::<std::io::Cursor<Vec<u8>>>(data) }, 0);
+
+        assert!(unsafe { custom_seek::<std::io::Cursor<Vec<u8>>>(data, 6, AR_SEEK_SET) });
+        let mut rest = [0u8; 5];
+        let n = unsafe {
+            custom_read::<std::io::Cursor<Vec<u8>>>(data, rest.as_mut_ptr() as *mut c_void, rest.len())
+        };
+        assert_eq!(n, 5);
+        assert_eq!(&rest, b"world");
+    }
+
+    #[test]
+    fn custom_read_and_tell_signal_hard_errors_distinct_from_eof() {
+        struct FailingReader;
+
+        impl Read for FailingReader {
+            fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::new(std::io::ErrorKind::Other, "boom"))
+            }
+        }
+
+        impl Seek for FailingReader {
+            fn seek(&mut self, _pos: SeekFrom) -> std::io::Result<u64> {
+                Err(std::io::Error::new(std::io::ErrorKind::Other, "boom"))
+            }
+        }
+
+        let mut boxed: Box<FailingReader> = Box::new(FailingReader);
+        let data = boxed.as_mut() as *mut FailingReader as *mut c_void;
+
+        let mut buf = [0u8; 4];
+        let n =
+            unsafe { custom_read::<FailingReader>(data, buf.as_mut_ptr() as *mut c_void, buf.len()) };
+        // (size_t)-1 signals a hard error; plain EOF would be 0
+        assert_eq!(n, size_t::MAX);
+
+        // -1 signals a hard error; a valid position is never negative
+        assert_eq!(unsafe { custom_tell::<FailingReader>(data) }, -1);
+    }
+
+    #[test]
+    fn is_dir_entry_name_detects_trailing_separator() {
+        assert!(is_dir_entry_name("sub/dir/"));
+        assert!(!is_dir_entry_name("sub/dir/file.txt"));
+    }
+
+    #[test]
+    fn sanitized_dest_rejects_absolute_and_parent_escapes() {
+        let tmp = std::env::temp_dir().join(format!("unarr_sanitize_{}", rand::random::<u64>()));
+        std::fs::create_dir_all(&tmp).unwrap();
+
+        assert!(sanitized_dest(&tmp, "/etc/passwd").is_err());
+        assert!(sanitized_dest(&tmp, "../escape.txt").is_err());
+        assert!(sanitized_dest(&tmp, "a/../../escape.txt").is_err());
+
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn sanitized_dest_rejects_names_with_no_real_path_segment() {
+        let tmp =
+            std::env::temp_dir().join(format!("unarr_sanitize_dot_{}", rand::random::<u64>()));
+        std::fs::create_dir_all(&tmp).unwrap();
+
+        // "." names dst itself, not anything under it, and used to panic
+        // in `sanitized_dest` instead of being rejected
+        assert!(sanitized_dest(&tmp, ".").is_err());
+        assert!(sanitized_dest(&tmp, "").is_err());
+
+        // a harmless trailing "." on an otherwise normal name just
+        // resolves under dst like any other nested entry
+        let target = sanitized_dest(&tmp, "sub/.").unwrap();
+        assert!(target.starts_with(tmp.canonicalize().unwrap()));
+
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn sanitized_dest_accepts_nested_name_under_dst() {
+        let tmp =
+            std::env::temp_dir().join(format!("unarr_sanitize_ok_{}", rand::random::<u64>()));
+        std::fs::create_dir_all(&tmp).unwrap();
+
+        let target = sanitized_dest(&tmp, "a/b/c.txt").unwrap();
+        assert!(target.starts_with(tmp.canonicalize().unwrap()));
+        assert_eq!(target.file_name().unwrap(), "c.txt");
+
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
 }